@@ -1,4 +1,9 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use bevy::core::FixedTimestep;
 use bevy::prelude::*;
+use bevy_ggrs::Rollback;
 
 use crate::game::bullet::{Bullet, Damage};
 use crate::game::enemy::{Enemy, EnemyFaction, Health};
@@ -6,25 +11,302 @@ use crate::game::player::{InvulnTimer, Player, PlayerFaction};
 use crate::game::starfield::Star;
 use crate::game::{GameState, WindowSize};
 
+/// Name of the fixed-step stage collision and invulnerability run in.
+/// Wiring it to a GGRS session (`with_rollback_schedule`, `register_rollback_type`
+/// for `Health`/`InvulnTimer`) is the app's top-level setup's job.
+pub const ROLLBACK_STAGE: &str = "collision_rollback_stage";
+
+/// Fixed simulation rate `ROLLBACK_STAGE` runs at. A GGRS session driving it
+/// must step at this same rate, or peers will desync.
+pub const ROLLBACK_FPS: u32 = 60;
+
+fn rollback_step() -> Duration {
+    Duration::from_secs_f64(1.0 / ROLLBACK_FPS as f64)
+}
+
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_set(
-            SystemSet::on_update(GameState::Playing)
-                .with_system(bound_player.system().after("move_player"))
-                .with_system(
-                    collide_with_enemy_bullets
-                        .system()
-                        .label("collide_with_enemy_bullets"),
-                )
-                .with_system(collide_with_player_bullets.system()),
+        app.init_resource::<SpatialGrid>()
+            .init_resource::<GrazeCount>()
+            .add_event::<BulletHitEvent>()
+            .add_startup_system(spawn_arena.system())
+            .add_stage(
+                ROLLBACK_STAGE,
+                SystemStage::parallel()
+                    .with_run_criteria(FixedTimestep::step(1.0 / ROLLBACK_FPS as f64))
+                    .with_system_set(
+                        SystemSet::on_update(GameState::Playing)
+                            .with_system(bound_player.system().label("bound_player"))
+                            .with_system(tick_invuln_timer.system())
+                            .with_system(
+                                build_spatial_grid
+                                    .system()
+                                    .label("build_spatial_grid")
+                                    .after("bound_player"),
+                            )
+                            .with_system(
+                                collide_with_enemy_bullets
+                                    .system()
+                                    .label("collide_with_enemy_bullets")
+                                    .after("build_spatial_grid"),
+                            )
+                            .with_system(
+                                collide_with_player_bullets
+                                    .system()
+                                    .label("collide_with_player_bullets")
+                                    .after("build_spatial_grid"),
+                            )
+                            .with_system(
+                                apply_player_hits
+                                    .system()
+                                    .after("collide_with_enemy_bullets"),
+                            )
+                            .with_system(
+                                apply_enemy_hits
+                                    .system()
+                                    .after("collide_with_player_bullets"),
+                            )
+                            .with_system(despawn_outside.system()),
+                    ),
+            )
+            .add_system(wrap_stars.system());
+    }
+}
+
+/// Which side fired the bullet responsible for a [`BulletHitEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+/// Fired by the collide systems whenever a bullet makes contact with a
+/// target, before any damage, audio, or state change has been applied.
+/// Downstream systems react to this instead of the detection code needing
+/// to know about every possible reaction.
+#[derive(Debug)]
+pub struct BulletHitEvent {
+    pub target: Entity,
+    pub bullet: Entity,
+    pub damage: u32,
+    pub faction: Faction,
+}
+
+/// Distance beyond the window edge the arena walls sit at. Equal to the
+/// old despawn-outside cull margin, so the wall-derived bounds line up
+/// with the window-relative bounds they replace.
+const ARENA_WALL_MARGIN: f32 = 12.0;
+
+/// Marks one of the four solid boundaries of the play area.
+#[derive(Debug)]
+pub struct AreaWall;
+
+/// Axis-aligned rectangle collider. Currently only carried by `AreaWall`
+/// entities, but generic enough for interior obstacles later.
+#[derive(Debug)]
+pub struct Collider {
+    pub half_width: f32,
+    pub half_height: f32,
+}
+
+/// Spawn the left/right/top/bottom arena walls sized from `WindowSize`.
+/// `bound_player` and `despawn_outside` read their bounds from these walls
+/// through `arena_outer_bounds`, which folds every `AreaWall` into one
+/// rectangle; an extra wall is treated as a boundary on its side of the
+/// origin, not as an interior obstacle, and `bound_player` takes every
+/// wall to be `ARENA_WALL_MARGIN` thick.
+fn spawn_arena(mut commands: Commands, window: Res<WindowSize>) {
+    let half_width = window.width / 2.0;
+    let half_height = window.height / 2.0;
+
+    let walls = [
+        (
+            Vec3::new(-(half_width + ARENA_WALL_MARGIN / 2.0), 0.0, 0.0),
+            Collider {
+                half_width: ARENA_WALL_MARGIN / 2.0,
+                half_height: half_height + ARENA_WALL_MARGIN,
+            },
+        ),
+        (
+            Vec3::new(half_width + ARENA_WALL_MARGIN / 2.0, 0.0, 0.0),
+            Collider {
+                half_width: ARENA_WALL_MARGIN / 2.0,
+                half_height: half_height + ARENA_WALL_MARGIN,
+            },
+        ),
+        (
+            Vec3::new(0.0, half_height + ARENA_WALL_MARGIN / 2.0, 0.0),
+            Collider {
+                half_width: half_width + ARENA_WALL_MARGIN,
+                half_height: ARENA_WALL_MARGIN / 2.0,
+            },
+        ),
+        (
+            Vec3::new(0.0, -(half_height + ARENA_WALL_MARGIN / 2.0), 0.0),
+            Collider {
+                half_width: half_width + ARENA_WALL_MARGIN,
+                half_height: ARENA_WALL_MARGIN / 2.0,
+            },
+        ),
+    ];
+
+    for (translation, collider) in walls {
+        commands
+            .spawn()
+            .insert(AreaWall)
+            .insert(collider)
+            .insert(Transform::from_translation(translation));
+    }
+}
+
+/// The arena's outer edge on each axis (min_x, max_x, min_y, max_y),
+/// derived from the wall colliders rather than recomputed from window
+/// dimensions. A wall's dominant axis (the one it's thin along) tells us
+/// which bound it constrains, and the sign of its position tells us which
+/// side.
+fn arena_outer_bounds(
+    walls: &Query<(&Collider, &Transform), With<AreaWall>>,
+) -> (f32, f32, f32, f32) {
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f32::MIN, f32::MAX, f32::MIN, f32::MAX);
+    for (collider, transform) in walls.iter() {
+        if collider.half_width < collider.half_height {
+            // Thin, tall wall: left or right.
+            if transform.translation.x < 0.0 {
+                min_x = min_x.max(transform.translation.x - collider.half_width);
+            } else {
+                max_x = max_x.min(transform.translation.x + collider.half_width);
+            }
+        } else {
+            // Thin, wide wall: top or bottom.
+            if transform.translation.y < 0.0 {
+                min_y = min_y.max(transform.translation.y - collider.half_height);
+            } else {
+                max_y = max_y.min(transform.translation.y + collider.half_height);
+            }
+        }
+    }
+    debug_assert!(
+        min_x != f32::MIN && max_x != f32::MAX && min_y != f32::MIN && max_y != f32::MAX,
+        "arena_outer_bounds didn't see all 4 axis-aligned walls; bound_player and \
+         despawn_outside would silently treat the missing side as unbounded"
+    );
+    (min_x, max_x, min_y, max_y)
+}
+
+/// Side length of a `SpatialGrid` cell, chosen to comfortably cover the
+/// widest pair of hitboxes likely to collide in a single check.
+const GRID_CELL_SIZE: f32 = 64.0;
+
+/// Uniform-grid broadphase used to narrow collision checks down from every
+/// bullet on screen to the handful sharing a neighborhood with the entity
+/// being tested.
+#[derive(Debug, Default)]
+pub struct SpatialGrid {
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+}
+
+impl SpatialGrid {
+    fn cell_of(position: Vec2) -> (i32, i32) {
+        (
+            (position.x / GRID_CELL_SIZE).floor() as i32,
+            (position.y / GRID_CELL_SIZE).floor() as i32,
         )
-        .add_system(despawn_outside.system())
-        .add_system(wrap_stars.system());
     }
+
+    fn insert(&mut self, entity: Entity, position: Vec2) {
+        self.cells
+            .entry(Self::cell_of(position))
+            .or_insert_with(Vec::new)
+            .push(entity);
+    }
+
+    /// Every entity in the 3x3 block of cells around `position`. Only
+    /// correct up to `GRID_CELL_SIZE`; use `nearby_within` for a wider
+    /// radius. Iteration order is arbitrary — sort with `sorted_by_rollback_id`.
+    fn nearby(&self, position: Vec2) -> HashSet<Entity> {
+        self.nearby_within(position, GRID_CELL_SIZE)
+    }
+
+    /// Every entity in the square block of cells that covers `radius` around
+    /// `position` — a superset of the entities actually within `radius`.
+    /// The block widens as needed so radii larger than `GRID_CELL_SIZE`
+    /// (e.g. a `GrazeBox`) aren't silently missed.
+    fn nearby_within(&self, position: Vec2, radius: f32) -> HashSet<Entity> {
+        let reach = ((radius / GRID_CELL_SIZE).ceil() as i32).max(1);
+        let (cx, cy) = Self::cell_of(position);
+        let mut entities = HashSet::new();
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                if let Some(bucket) = self.cells.get(&(cx + dx, cy + dy)) {
+                    entities.extend(bucket.iter().copied());
+                }
+            }
+        }
+        entities
+    }
+}
+
+/// Order candidate entities by stable `Rollback` id so every peer resolves
+/// hits in the same sequence. Entities without `Rollback` sort last, tied
+/// by `Entity::id` rather than arbitrary `HashSet` order.
+fn sorted_by_rollback_id(entities: HashSet<Entity>, rollback: &Query<&Rollback>) -> Vec<Entity> {
+    let mut entities: Vec<Entity> = entities.into_iter().collect();
+    entities.sort_by_key(|entity| rollback_sort_key(*entity, rollback));
+    entities
+}
+
+fn rollback_sort_key(entity: Entity, rollback: &Query<&Rollback>) -> (u32, u32) {
+    let rollback_id = rollback.get(entity).map(|r| r.id()).unwrap_or(u32::MAX);
+    (rollback_id, entity.id())
+}
+
+fn build_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    bullets: Query<(Entity, &Hitbox, &Transform), With<Bullet>>,
+) {
+    grid.cells.clear();
+    for (entity, hitbox, transform) in bullets.iter() {
+        debug_assert!(
+            hitbox.radius <= GRID_CELL_SIZE / 2.0,
+            "bullet hitbox radius {} exceeds GRID_CELL_SIZE / 2 ({}); nearby() will miss hits beyond its 3x3 block",
+            hitbox.radius,
+            GRID_CELL_SIZE / 2.0,
+        );
+        grid.insert(entity, transform.translation.truncate());
+    }
+}
+
+/// A larger radius around the player used to reward close dodges. Distinct
+/// from `Hitbox`, which determines when a bullet actually hits the player.
+#[derive(Debug)]
+pub struct GrazeBox {
+    pub radius: f32,
 }
 
+/// Marks an enemy bullet that has already been counted as a graze, so a
+/// bullet lingering near the player across several frames is only counted
+/// once.
+#[derive(Debug)]
+pub struct Grazed;
+
+/// Tracks how many enemy bullets the player has grazed this run.
+#[derive(Debug, Default)]
+pub struct GrazeCount(pub u32);
+
+/// Lets a bullet survive a fixed number of hits instead of despawning on
+/// first contact, for laser/penetrating weapon archetypes.
+#[derive(Debug)]
+pub struct Pierce {
+    pub remaining: u32,
+}
+
+/// Enemies a piercing bullet has already hit, so it can't damage the same
+/// enemy twice while carrying on through the rest.
+#[derive(Debug, Default)]
+pub struct HitList(pub HashSet<Entity>);
+
 #[derive(Debug)]
 pub struct DespawnOutside;
 
@@ -60,67 +342,149 @@ pub fn outer_bound(dimension: f32, sprite: f32) -> f32 {
 }
 
 fn bound_player(
-    window: Res<WindowSize>,
+    walls: Query<(&Collider, &Transform), With<AreaWall>>,
     mut query: Query<(&SpriteSize, &mut Transform), With<Player>>,
 ) {
+    let (outer_min_x, outer_max_x, outer_min_y, outer_max_y) = arena_outer_bounds(&walls);
+    let min_x = outer_min_x + ARENA_WALL_MARGIN;
+    let max_x = outer_max_x - ARENA_WALL_MARGIN;
+    let min_y = outer_min_y + ARENA_WALL_MARGIN;
+    let max_y = outer_max_y - ARENA_WALL_MARGIN;
+
     for (sprite, mut transform) in query.iter_mut() {
-        let width = inner_bound(window.width, sprite.width);
-        let height = inner_bound(window.height, sprite.height);
-        transform.translation.x = transform.translation.x.min(width).max(-width);
-        transform.translation.y = transform.translation.y.min(height).max(-height);
+        let half_width = sprite.width / 2.0;
+        let half_height = sprite.height / 2.0;
+        transform.translation.x = transform
+            .translation
+            .x
+            .min(max_x - half_width)
+            .max(min_x + half_width);
+        transform.translation.y = transform
+            .translation
+            .y
+            .min(max_y - half_height)
+            .max(min_y + half_height);
     }
 }
 
+/// Tick the player's invulnerability timer by the fixed `ROLLBACK_FPS` step,
+/// independent of whether a bullet lands this frame.
+fn tick_invuln_timer(mut player: Query<&mut InvulnTimer, With<Player>>) {
+    let mut invuln_timer = player.single_mut().expect("expected a single player");
+    invuln_timer.tick(rollback_step());
+}
+
 fn collide_with_enemy_bullets(
     mut commands: Commands,
-    server: Res<AssetServer>,
-    audio: Res<Audio>,
-    mut state: ResMut<State<GameState>>,
-    time: Res<Time>,
-    bullets: Query<(Entity, &Damage, &Hitbox, &Transform), (With<Bullet>, With<EnemyFaction>)>,
-    mut player: Query<(&mut Health, &Hitbox, &mut InvulnTimer, &Transform), With<Player>>,
+    mut hit_events: EventWriter<BulletHitEvent>,
+    mut graze_count: ResMut<GrazeCount>,
+    grid: Res<SpatialGrid>,
+    rollback_ids: Query<&Rollback>,
+    bullets: Query<
+        (Entity, &Damage, &Hitbox, &Transform, Option<&Grazed>),
+        (With<Bullet>, With<EnemyFaction>),
+    >,
+    player: Query<(Entity, &Hitbox, &GrazeBox, &Transform), With<Player>>,
 ) {
-    let (mut health, player_hitbox, mut invuln_timer, player_transform) =
-        player.single_mut().expect("expected a single player");
+    let (player_entity, player_hitbox, graze_box, player_transform) =
+        player.single().expect("expected a single player");
+
+    // The graze radius is deliberately wider than the hit radius, and can
+    // exceed a single grid cell — widen the broadphase search to match
+    // rather than silently missing far-but-still-grazing bullets. Bullet
+    // hitboxes are at most `GRID_CELL_SIZE / 2` (checked by the
+    // `debug_assert!` in `build_spatial_grid`), so padding by that much
+    // covers every bullet the graze check could possibly care about.
+    let search_radius = graze_box.radius + GRID_CELL_SIZE / 2.0;
+    let nearby = grid.nearby_within(player_transform.translation.truncate(), search_radius);
+    for entity in sorted_by_rollback_id(nearby, &rollback_ids) {
+        let (entity, damage, hitbox, transform, grazed) = match bullets.get(entity) {
+            Ok(bullet) => bullet,
+            Err(_) => continue,
+        };
 
-    // Tick invulnerability timer.
-    invuln_timer.tick(time.delta());
-    for (entity, damage, hitbox, transform) in bullets.iter() {
         // Check for collision.
         let distance = player_transform
             .translation
             .truncate()
             .distance_squared(transform.translation.truncate());
-        let radius_sum = player_hitbox.radius + hitbox.radius;
-        if distance < radius_sum * radius_sum {
+        let hit_radius_sum = player_hitbox.radius + hitbox.radius;
+        if distance < hit_radius_sum * hit_radius_sum {
             commands.entity(entity).despawn();
+            hit_events.send(BulletHitEvent {
+                target: player_entity,
+                bullet: entity,
+                damage: damage.0,
+                faction: Faction::Enemy,
+            });
+            continue;
+        }
 
-            // Check if currently vulnerable.
-            if invuln_timer.finished() {
-                // Play audio.
-                let sound = server.load("sounds/player_hit.wav");
-                audio.play(sound);
-
-                // Deal damage.
-                health.damage(damage.0);
-                if health.current == 0 {
-                    state.set(GameState::GameOver).unwrap();
-                }
-
-                // Reset invulnerability timer.
-                invuln_timer.reset();
-            }
+        // Check for a graze: close, but not a hit, and not already counted.
+        let graze_radius_sum = graze_box.radius + hitbox.radius;
+        if grazed.is_none() && distance < graze_radius_sum * graze_radius_sum {
+            commands.entity(entity).insert(Grazed);
+            graze_count.0 += 1;
         }
     }
 }
 
 fn collide_with_player_bullets(
     mut commands: Commands,
-    bullets: Query<(Entity, &Damage, &Hitbox, &Transform), (With<Bullet>, With<PlayerFaction>)>,
-    mut enemies: Query<(&mut Health, &Hitbox, &Transform), With<Enemy>>,
+    mut hit_events: EventWriter<BulletHitEvent>,
+    grid: Res<SpatialGrid>,
+    rollback_ids: Query<&Rollback>,
+    mut bullets: Query<
+        (
+            Entity,
+            &Damage,
+            &Hitbox,
+            &Transform,
+            Option<&mut Pierce>,
+            Option<&mut HitList>,
+        ),
+        (With<Bullet>, With<PlayerFaction>),
+    >,
+    enemies: Query<(Entity, &Hitbox, &Transform), With<Enemy>>,
 ) {
-    for (mut health, enemy_hitbox, enemy_transform) in enemies.iter_mut() {
-        for (entity, damage, hitbox, transform) in bullets.iter() {
+    let mut enemy_entities: Vec<Entity> = enemies.iter().map(|(entity, _, _)| entity).collect();
+    enemy_entities.sort_by_key(|entity| rollback_sort_key(*entity, &rollback_ids));
+
+    for enemy_entity in enemy_entities {
+        let (enemy_entity, enemy_hitbox, enemy_transform) = match enemies.get(enemy_entity) {
+            Ok(enemy) => enemy,
+            Err(_) => continue,
+        };
+        debug_assert!(
+            enemy_hitbox.radius <= GRID_CELL_SIZE / 2.0,
+            "enemy hitbox radius {} exceeds GRID_CELL_SIZE / 2 ({}); nearby() will miss hits beyond its 3x3 block",
+            enemy_hitbox.radius,
+            GRID_CELL_SIZE / 2.0,
+        );
+        let nearby = grid.nearby(enemy_transform.translation.truncate());
+        for entity in sorted_by_rollback_id(nearby, &rollback_ids) {
+            let (entity, damage, hitbox, transform, pierce, hit_list) =
+                match bullets.get_mut(entity) {
+                    Ok(bullet) => bullet,
+                    Err(_) => continue,
+                };
+
+            // Skip enemies this bullet has already pierced through.
+            if let Some(hit_list) = &hit_list {
+                if hit_list.0.contains(&enemy_entity) {
+                    continue;
+                }
+            }
+
+            // An exhausted pierce budget must stop registering hits right
+            // away — the despawn below is a deferred command, so without
+            // this check a bullet that hit its last enemy earlier this
+            // same frame would still be "alive" for every enemy processed
+            // afterward and pierce past its limit.
+            if pierce.as_ref().map_or(false, |p| p.remaining == 0) {
+                continue;
+            }
+
             // Check for collision.
             let distance = enemy_transform
                 .translation
@@ -128,38 +492,105 @@ fn collide_with_player_bullets(
                 .distance_squared(transform.translation.truncate());
             let radius_sum = enemy_hitbox.radius + hitbox.radius;
             if distance < radius_sum * radius_sum {
-                commands.entity(entity).despawn();
-                health.damage(damage.0);
+                if let Some(mut hit_list) = hit_list {
+                    hit_list.0.insert(enemy_entity);
+                }
+
+                match pierce {
+                    Some(mut pierce) => {
+                        pierce.remaining = pierce.remaining.saturating_sub(1);
+                        if pierce.remaining == 0 {
+                            commands.entity(entity).despawn();
+                        }
+                    }
+                    // Bullets without `Pierce` keep the original despawn-on-first-hit behavior.
+                    None => commands.entity(entity).despawn(),
+                }
+
+                hit_events.send(BulletHitEvent {
+                    target: enemy_entity,
+                    bullet: entity,
+                    damage: damage.0,
+                    faction: Faction::Player,
+                });
             }
         }
     }
 }
 
+/// React to bullets that hit the player: play the hit sound, apply damage,
+/// reset invulnerability, and transition to game over, but only while the
+/// player is actually vulnerable.
+fn apply_player_hits(
+    server: Res<AssetServer>,
+    audio: Res<Audio>,
+    mut state: ResMut<State<GameState>>,
+    mut hit_events: EventReader<BulletHitEvent>,
+    mut player: Query<(&mut Health, &mut InvulnTimer), With<Player>>,
+) {
+    let (mut health, mut invuln_timer) = player.single_mut().expect("expected a single player");
+    for event in hit_events.iter() {
+        if event.faction != Faction::Enemy || !invuln_timer.finished() {
+            continue;
+        }
+
+        // Play audio.
+        let sound = server.load("sounds/player_hit.wav");
+        audio.play(sound);
+
+        // Deal damage.
+        health.damage(event.damage);
+        if health.current == 0 {
+            state.set(GameState::GameOver).unwrap();
+        }
+
+        // Reset invulnerability timer.
+        invuln_timer.reset();
+    }
+}
+
+/// React to bullets that hit an enemy by applying damage.
+fn apply_enemy_hits(
+    mut hit_events: EventReader<BulletHitEvent>,
+    mut enemies: Query<&mut Health, With<Enemy>>,
+) {
+    for event in hit_events.iter() {
+        if event.faction != Faction::Player {
+            continue;
+        }
+        if let Ok(mut health) = enemies.get_mut(event.target) {
+            health.damage(event.damage);
+        }
+    }
+}
+
 fn despawn_outside(
     mut commands: Commands,
-    window: Res<WindowSize>,
+    walls: Query<(&Collider, &Transform), With<AreaWall>>,
     sprite_sheets: Query<(Entity, &SpriteSize, &Transform), With<DespawnOutside>>,
     sprites: Query<(Entity, &Sprite, &Transform), With<DespawnOutside>>,
 ) {
+    let (min_x, max_x, min_y, max_y) = arena_outer_bounds(&walls);
+
     for (entity, sprite, transform) in sprite_sheets.iter() {
-        let width = outer_bound(window.width, sprite.width) + 12.0;
-        let height = outer_bound(window.height, sprite.height) + 12.0;
-        if transform.translation.x > width
-            || transform.translation.x < -width
-            || transform.translation.y > height
-            || transform.translation.y < -height
+        let half_width = sprite.width / 2.0;
+        let half_height = sprite.height / 2.0;
+        if transform.translation.x > max_x + half_width
+            || transform.translation.x < min_x - half_width
+            || transform.translation.y > max_y + half_height
+            || transform.translation.y < min_y - half_height
         {
             commands.entity(entity).despawn();
         }
     }
 
     for (entity, sprite, transform) in sprites.iter() {
-        let width = outer_bound(window.width, sprite.size.x * transform.scale.x) + 12.0;
-        let height = outer_bound(window.height, sprite.size.y * transform.scale.y) + 12.0;
-        if transform.translation.x > width
-            || transform.translation.x < -width
-            || transform.translation.y > height
-            || transform.translation.y < -height
+        let half_width = sprite.size.x * transform.scale.x / 2.0;
+        let half_height = sprite.size.y * transform.scale.y / 2.0;
+        if transform.translation.x > max_x + half_width
+            || transform.translation.x < min_x - half_width
+            || transform.translation.y > max_y + half_height
+            || transform.translation.y < min_y - half_height
         {
             commands.entity(entity).despawn();
         }
@@ -174,3 +605,82 @@ fn wrap_stars(window: Res<WindowSize>, mut query: Query<(&Sprite, &mut Transform
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Brute-force search for every entity within `radius` of `position`,
+    /// used as the ground truth the grid's `nearby` must be a superset of.
+    fn naive_within_radius(
+        entities: &[(Entity, Vec2)],
+        position: Vec2,
+        radius: f32,
+    ) -> HashSet<Entity> {
+        entities
+            .iter()
+            .filter(|(_, p)| p.distance_squared(position) < radius * radius)
+            .map(|(entity, _)| *entity)
+            .collect()
+    }
+
+    /// Deterministically scatter `count` entities across a few-thousand-unit
+    /// square, seeded so every test run sees the same layout.
+    fn seeded_entities(seed: u32, count: u32) -> Vec<(Entity, Vec2)> {
+        let mut seed = seed;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            seed
+        };
+        (0..count)
+            .map(|i| {
+                let x = (next() % 4000) as f32 - 2000.0;
+                let y = (next() % 4000) as f32 - 2000.0;
+                (Entity::new(i), Vec2::new(x, y))
+            })
+            .collect()
+    }
+
+    fn grid_from(entities: &[(Entity, Vec2)]) -> SpatialGrid {
+        let mut grid = SpatialGrid::default();
+        for (entity, position) in entities {
+            grid.insert(*entity, *position);
+        }
+        grid
+    }
+
+    #[test]
+    fn grid_nearby_matches_naive_scan() {
+        let entities = seeded_entities(0x1234_5678, 4_000);
+        let grid = grid_from(&entities);
+
+        // A bullet can only collide within a radius well inside one cell, so
+        // the 3x3 neighborhood the grid checks must contain every true hit.
+        let probe_points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(500.0, -500.0),
+            Vec2::new(-1999.0, 1999.0),
+        ];
+        let hit_radius = GRID_CELL_SIZE / 2.0;
+        for probe in probe_points {
+            let expected = naive_within_radius(&entities, probe, hit_radius);
+            let nearby = grid.nearby(probe);
+            assert!(expected.is_subset(&nearby));
+        }
+    }
+
+    #[test]
+    fn nearby_within_covers_graze_sized_radii_beyond_one_cell() {
+        let entities = seeded_entities(0x0bad_f00d, 4_000);
+        let grid = grid_from(&entities);
+
+        // A graze radius comfortably exceeds a single cell; the plain 3x3
+        // `nearby` is documented to only be correct up to `GRID_CELL_SIZE`,
+        // so anything wider must go through `nearby_within` instead.
+        let graze_radius = GRID_CELL_SIZE * 2.5;
+        let probe = Vec2::new(137.0, -842.0);
+        let expected = naive_within_radius(&entities, probe, graze_radius);
+        let nearby = grid.nearby_within(probe, graze_radius);
+        assert!(expected.is_subset(&nearby));
+    }
+}